@@ -0,0 +1,775 @@
+//! Author: Stewart Charles
+//!
+//! Numerical approximation of ordinary differential equations: fixed-step and adaptive
+//! integrators, batch solving over a worker pool, trajectory/dense output, benchmarking,
+//! and an optional C FFI surface.
+
+/* A source of monotonic-ish seconds. The solvers used to call `time::precise_time_s` directly,
+   which makes the reported elapsed seconds non-deterministic and hard-couples us to the `time`
+   crate. Injecting a `Clock` lets tests feed a deterministic value and lets embedded callers
+   supply a cycle counter instead. */
+pub mod clock {
+    extern crate time;
+
+    pub trait Clock {
+        /* Current time in seconds. Only differences between two readings are meaningful. */
+        fn now(&self) -> f64;
+    }
+
+    /* The production clock, wrapping the wall-clock reading the crate has always used. */
+    pub struct RealClock;
+
+    impl Clock for RealClock {
+        fn now(&self) -> f64 {
+            time::precise_time_s()
+        }
+    }
+
+    /* A clock the caller advances by hand, so elapsed timings become reproducible. */
+    pub struct MockClock {
+        seconds: std::cell::Cell<f64>,
+    }
+
+    impl MockClock {
+        pub fn new(start: f64) -> MockClock {
+            MockClock { seconds: std::cell::Cell::new(start) }
+        }
+
+        /* Move the clock forward; the next `now` reading reflects the advance. */
+        pub fn advance(&self, delta: f64) {
+            self.seconds.set(self.seconds.get() + delta);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> f64 {
+            self.seconds.get()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn mock_clock_reports_and_advances() {
+            let clock = MockClock::new(10.0);
+            assert_eq!(clock.now(), 10.0);
+            clock.advance(2.5);
+            assert_eq!(clock.now(), 12.5);
+        }
+    }
+}
+
+pub mod threaded_funcs {
+    use std::sync::Arc;
+    use super::clock::Clock;
+
+    /* The derivative of a first-order system: given `t` and the current state slice, write dy/dt into
+       `out`. A scalar equation is just the one-element case, handled by the `*_scalar` wrappers below. */
+    pub fn euler_method<F:Fn(f64,&[f64],&mut [f64]), C:Clock>(y0:&[f64], t0:f64, steps:usize, h:f64, de:Arc<F>, clock:&C)-> (Vec<f64>,f64) {
+        let start = clock.now();
+        let dim = y0.len();
+        let mut y = y0.to_vec();
+        let mut tcurrent = t0;
+        /* One scratch buffer for the slope, allocated once rather than every step. */
+        let mut k1 = vec![0.0; dim];
+        for _ in 0..steps {
+            de(tcurrent, &y, &mut k1);
+            for i in 0..dim {
+                y[i] += h*k1[i];
+            }
+            tcurrent += h;
+        }
+        let end = clock.now() - start;
+        (y,end)
+    }
+
+    /* Also known as the Heun method for approximation*/
+    pub fn improved_euler<F:Fn(f64,&[f64],&mut [f64]), C:Clock>(y0:&[f64], t0:f64, steps:usize, h:f64, de:Arc<F>, clock:&C) -> (Vec<f64>,f64) {
+        let start = clock.now();
+        let dim = y0.len();
+        let mut y = y0.to_vec();
+        let mut tcurrent = t0;
+        let half_h = h/2.0;
+        let mut k1 = vec![0.0; dim];
+        let mut k2 = vec![0.0; dim];
+        let mut tmp = vec![0.0; dim];
+        for _ in 0..steps {
+            de(tcurrent, &y, &mut k1);
+            for i in 0..dim {
+                tmp[i] = y[i] + h*k1[i];
+            }
+            de(tcurrent + h, &tmp, &mut k2);
+            for i in 0..dim {
+                y[i] += half_h*(k1[i] + k2[i]);
+            }
+            tcurrent += h;
+        }
+        let end = clock.now() - start;
+        (y,end)
+    }
+
+    pub fn runge_kutta<F:Fn(f64,&[f64],&mut [f64]), C:Clock>(y0:&[f64], t0:f64, steps:usize, h:f64, de:Arc<F>, clock:&C)-> (Vec<f64>,f64) {
+        let start = clock.now();
+        let dim = y0.len();
+        let mut y = y0.to_vec();
+        let mut tcurrent = t0;
+
+        /* Precompute division operations outside of loop for efficiency*/
+        let half_h = h/2.0;
+        let sixth_h = h/6.0;
+        /* Preallocate the four stage buffers plus one temporary argument buffer. */
+        let mut k1 = vec![0.0; dim];
+        let mut k2 = vec![0.0; dim];
+        let mut k3 = vec![0.0; dim];
+        let mut k4 = vec![0.0; dim];
+        let mut tmp = vec![0.0; dim];
+        for _ in 0..steps {
+            de(tcurrent, &y, &mut k1);
+            for i in 0..dim { tmp[i] = y[i] + half_h*k1[i]; }
+            de(tcurrent + half_h, &tmp, &mut k2);
+            for i in 0..dim { tmp[i] = y[i] + half_h*k2[i]; }
+            de(tcurrent + half_h, &tmp, &mut k3);
+            for i in 0..dim { tmp[i] = y[i] + h*k3[i]; }
+            de(tcurrent + h, &tmp, &mut k4);
+            for i in 0..dim {
+                y[i] += sixth_h*(k1[i] + 2.0*k2[i] + 2.0*k3[i] + k4[i]);
+            }
+            tcurrent += h;
+        }
+        let end = clock.now() - start;
+        (y,end)
+    }
+
+    /* Scalar convenience wrappers that adapt a `Fn(f64,f64)->f64` DE into the one-element system form,
+       preserving the original single-equation API the rest of the crate was built around. */
+    pub fn euler_method_scalar<F:Fn(f64,f64)->f64, C:Clock>(y0:f64, t0:f64, steps:usize, h:f64, de:Arc<F>, clock:&C)-> (f64,f64) {
+        let wrapped = Arc::new(move |t:f64, y:&[f64], out:&mut [f64]| out[0] = de(t, y[0]));
+        let (y, end) = euler_method(&[y0], t0, steps, h, wrapped, clock);
+        (y[0], end)
+    }
+
+    pub fn improved_euler_scalar<F:Fn(f64,f64)->f64, C:Clock>(y0:f64, t0:f64, steps:usize, h:f64, de:Arc<F>, clock:&C)-> (f64,f64) {
+        let wrapped = Arc::new(move |t:f64, y:&[f64], out:&mut [f64]| out[0] = de(t, y[0]));
+        let (y, end) = improved_euler(&[y0], t0, steps, h, wrapped, clock);
+        (y[0], end)
+    }
+
+    pub fn runge_kutta_scalar<F:Fn(f64,f64)->f64, C:Clock>(y0:f64, t0:f64, steps:usize, h:f64, de:Arc<F>, clock:&C)-> (f64,f64) {
+        let wrapped = Arc::new(move |t:f64, y:&[f64], out:&mut [f64]| out[0] = de(t, y[0]));
+        let (y, end) = runge_kutta(&[y0], t0, steps, h, wrapped, clock);
+        (y[0], end)
+    }
+
+    /* Adaptive Runge-Kutta-Fehlberg (RK45). Rather than forcing the caller to guess a step 'h'
+       small enough to be accurate everywhere, we embed a 4th and a 5th order estimate in the same
+       six stage evaluations and let the local error drive the step size: cheap where the solution
+       is smooth, fine where it is stiff.
+
+       Returns the final approximation, the elapsed seconds, and the number of accepted and rejected
+       steps so the caller can see how much work the adaptivity saved over the fixed-step loop. */
+    pub fn runge_kutta_adaptive<F:Fn(f64,f64)->f64, C:Clock>(y0:f64, t0:f64, t_end:f64, tol:f64, de:Arc<F>, clock:&C)-> (f64,f64,usize,usize) {
+        let start = clock.now();
+        let mut ycurrent = y0;
+        let mut tcurrent = t0;
+        /* A coarse first guess; the controller corrects it within the first few steps. */
+        let mut h = (t_end - t0) / 100.0;
+        let mut accepted = 0;
+        let mut rejected = 0;
+        while tcurrent < t_end {
+            /* Never step past the end of the integration range. */
+            if tcurrent + h > t_end {
+                h = t_end - tcurrent;
+            }
+            /* The six Fehlberg stages, scaled by h so they are increments in y. */
+            let k1 = h*de(tcurrent, ycurrent);
+            let k2 = h*de(tcurrent + h/4.0, ycurrent + k1/4.0);
+            let k3 = h*de(tcurrent + 3.0*h/8.0, ycurrent + 3.0*k1/32.0 + 9.0*k2/32.0);
+            let k4 = h*de(tcurrent + 12.0*h/13.0, ycurrent + 1932.0*k1/2197.0 - 7200.0*k2/2197.0 + 7296.0*k3/2197.0);
+            let k5 = h*de(tcurrent + h, ycurrent + 439.0*k1/216.0 - 8.0*k2 + 3680.0*k3/513.0 - 845.0*k4/4104.0);
+            let k6 = h*de(tcurrent + h/2.0, ycurrent - 8.0*k1/27.0 + 2.0*k2 - 3544.0*k3/2565.0 + 1859.0*k4/4104.0 - 11.0*k5/40.0);
+            let y4 = ycurrent + 25.0*k1/216.0 + 1408.0*k3/2565.0 + 2197.0*k4/4104.0 - k5/5.0;
+            let y5 = ycurrent + 16.0*k1/135.0 + 6656.0*k3/12825.0 + 28561.0*k4/56430.0 - 9.0*k5/50.0 + 2.0*k6/55.0;
+            let err = (y5 - y4).abs();
+            if err <= tol {
+                tcurrent += h;
+                ycurrent = y5;
+                accepted += 1;
+            } else {
+                rejected += 1;
+            }
+            /* Rescale for the next attempt regardless of accept/reject, clamped so a single bad
+               estimate cannot shrink or grow the step too violently. */
+            let h_new = 0.9 * h * (tol / err.max(1e-12)).powf(0.2);
+            h = h_new.max(0.1*h).min(5.0*h);
+        }
+        let end = clock.now() - start;
+        (ycurrent,end,accepted,rejected)
+    }
+
+    /* Trace variant of Euler that keeps the sampled solution instead of discarding everything but the
+       endpoint. `sample_stride` throttles how often a point is kept, so a 100M-step integration can be
+       stored as, say, every 10000th point rather than exhausting memory. The initial point is always
+       recorded. */
+    pub fn euler_method_trace<F:Fn(f64,f64)->f64>(y0:f64, t0:f64, steps:usize, h:f64, sample_stride:usize, de:Arc<F>)-> Vec<(f64,f64)> {
+        let stride = sample_stride.max(1);
+        let mut trace = Vec::with_capacity(steps / stride + 1);
+        let mut ycurrent = y0;
+        let mut tcurrent = t0;
+        trace.push((tcurrent, ycurrent));
+        for step in 0..steps {
+            ycurrent += h*de(tcurrent, ycurrent);
+            tcurrent += h;
+            if (step + 1) % stride == 0 {
+                trace.push((tcurrent, ycurrent));
+            }
+        }
+        trace
+    }
+
+    /* Trace variant of Runge-Kutta, sampled the same way as `euler_method_trace`. For continuous
+       (dense) output between nodes, use `runge_kutta_dense` instead. */
+    pub fn runge_kutta_trace<F:Fn(f64,f64)->f64>(y0:f64, t0:f64, steps:usize, h:f64, sample_stride:usize, de:Arc<F>)-> Vec<(f64,f64)> {
+        let stride = sample_stride.max(1);
+        let half_h = h/2.0;
+        let sixth_h = h/6.0;
+        let mut trace = Vec::with_capacity(steps / stride + 1);
+        let mut ycurrent = y0;
+        let mut tcurrent = t0;
+        trace.push((tcurrent, ycurrent));
+        for step in 0..steps {
+            let k1 = de(tcurrent, ycurrent);
+            let k2 = de(tcurrent + half_h, ycurrent + half_h*k1);
+            let k3 = de(tcurrent + half_h, ycurrent + half_h*k2);
+            let k4 = de(tcurrent + h, ycurrent + h*k3);
+            ycurrent += sixth_h*(k1 + 2.0*k2 + 2.0*k3 + k4);
+            tcurrent += h;
+            if (step + 1) % stride == 0 {
+                trace.push((tcurrent, ycurrent));
+            }
+        }
+        trace
+    }
+
+    /* One integration step's worth of dense-output data: the interval `[t0, t0+h]`, its endpoint
+       values, and the endpoint slopes `f0` (= k1, the slope at t0) and `f1` (= k4, the slope at the
+       right node). A cubic Hermite polynomial through these four quantities reproduces the solution to
+       the method's order anywhere inside the interval. */
+    struct HermiteSegment {
+        t0: f64,
+        h: f64,
+        y0: f64,
+        y1: f64,
+        f0: f64,
+        f1: f64,
+    }
+
+    /* Continuous output over the whole integration range: the ordered list of per-step Hermite
+       segments, queryable at any `t` without re-running the solver. */
+    pub struct DenseOutput {
+        segments: Vec<HermiteSegment>,
+    }
+
+    impl DenseOutput {
+        /* Evaluate the interpolated solution at an arbitrary `t`, clamped to the integrated range. */
+        pub fn eval(&self, t: f64) -> f64 {
+            if self.segments.is_empty() {
+                return 0.0;
+            }
+            /* Locate the segment containing t (linear scan is fine; callers query far fewer points
+               than there are steps). */
+            let mut seg = &self.segments[0];
+            for candidate in &self.segments {
+                if t >= candidate.t0 {
+                    seg = candidate;
+                } else {
+                    break;
+                }
+            }
+            let s = ((t - seg.t0) / seg.h).clamp(0.0, 1.0);
+            let s2 = s*s;
+            let s3 = s2*s;
+            /* Standard cubic Hermite basis on the unit interval. */
+            let h00 = 2.0*s3 - 3.0*s2 + 1.0;
+            let h10 = s3 - 2.0*s2 + s;
+            let h01 = -2.0*s3 + 3.0*s2;
+            let h11 = s3 - s2;
+            h00*seg.y0 + h10*seg.h*seg.f0 + h01*seg.y1 + h11*seg.h*seg.f1
+        }
+    }
+
+    /* Runge-Kutta integration that additionally records a Hermite segment per step, reusing the `k1`
+       and `k4` slopes the method already computes so dense output costs no extra derivative calls. */
+    pub fn runge_kutta_dense<F:Fn(f64,f64)->f64>(y0:f64, t0:f64, steps:usize, h:f64, de:Arc<F>)-> DenseOutput {
+        let half_h = h/2.0;
+        let sixth_h = h/6.0;
+        let mut ycurrent = y0;
+        let mut tcurrent = t0;
+        let mut segments = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            let k1 = de(tcurrent, ycurrent);
+            let k2 = de(tcurrent + half_h, ycurrent + half_h*k1);
+            let k3 = de(tcurrent + half_h, ycurrent + half_h*k2);
+            let k4 = de(tcurrent + h, ycurrent + h*k3);
+            let ynext = ycurrent + sixth_h*(k1 + 2.0*k2 + 2.0*k3 + k4);
+            segments.push(HermiteSegment { t0: tcurrent, h, y0: ycurrent, y1: ynext, f0: k1, f1: k4 });
+            ycurrent = ynext;
+            tcurrent += h;
+        }
+        DenseOutput { segments }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use super::super::clock::MockClock;
+
+        /* Feeding a deterministic clock makes the numerical result exactly reproducible: a constant
+           slope of 1.0 integrated with Euler over 10 steps of 0.5 must land precisely on 5.0, and the
+           reported elapsed time must be the mock's (unadvanced) zero rather than a wall-clock reading. */
+        #[test]
+        fn euler_scalar_is_exact_under_a_mock_clock() {
+            let clock = MockClock::new(0.0);
+            let de = Arc::new(|_t: f64, _y: f64| 1.0);
+            let (y, elapsed) = euler_method_scalar(0.0, 0.0, 10, 0.5, de, &clock);
+            assert_eq!(y, 5.0);
+            assert_eq!(elapsed, 0.0);
+        }
+
+        /* With a per-step tolerance of 1e-8, the adaptive RKF45 controller should solve dy/dt = y
+           from y(0)=1 to within 1e-4 of y(1)=e, accepting at least one step along the way. */
+        #[test]
+        fn adaptive_matches_exponential() {
+            let clock = MockClock::new(0.0);
+            let de = Arc::new(|_t: f64, y: f64| y);
+            let (y, _elapsed, accepted, _rejected) =
+                runge_kutta_adaptive(1.0, 0.0, 1.0, 1e-8, de, &clock);
+            assert!((y - std::f64::consts::E).abs() < 1e-4);
+            assert!(accepted > 0);
+        }
+
+        /* The trace keeps the initial point plus every `sample_stride`-th step, and its final sample
+           matches the endpoint a plain solve would return. */
+        #[test]
+        fn euler_trace_samples_at_stride() {
+            let de = Arc::new(|_t: f64, _y: f64| 1.0);
+            let trace = euler_method_trace(0.0, 0.0, 10, 0.5, 2, de);
+            assert_eq!(trace.len(), 6);
+            assert_eq!(trace[0], (0.0, 0.0));
+            assert_eq!(trace[trace.len() - 1], (5.0, 5.0));
+        }
+
+        /* Dense output of a linear solution (constant slope) is reproduced exactly by the Hermite
+           interpolant, including at points strictly between the integration nodes. */
+        #[test]
+        fn dense_output_interpolates_between_nodes() {
+            let de = Arc::new(|_t: f64, _y: f64| 1.0);
+            let dense = runge_kutta_dense(0.0, 0.0, 10, 0.5, de);
+            assert!((dense.eval(2.5) - 2.5).abs() < 1e-9);
+            assert!((dense.eval(2.75) - 2.75).abs() < 1e-9);
+        }
+    }
+}
+
+/* Version of the Approximation functions that accept  a reference to a function.*/
+pub mod serial_funcs {
+    use super::clock::Clock;
+
+    #[allow(unused_variables)]
+    pub fn regular_de(t:f64,y:f64)->f64 {
+        10.0 - 0.2 * y - 0.27 * y.powf(1.5)
+    }
+
+    pub fn euler_method<F:Fn(f64,&[f64],&mut [f64]), C:Clock>(y0:&[f64],t0:f64,steps:usize, h:f64,de:&F, clock:&C)-> (Vec<f64>,f64) {
+        let start = clock.now();
+        let dim = y0.len();
+        let mut y = y0.to_vec();
+        let mut tcurrent = t0;
+        let mut k1 = vec![0.0; dim];
+        for _ in 0..steps {
+            de(tcurrent, &y, &mut k1);
+            for i in 0..dim {
+                y[i] += h*k1[i];
+            }
+            tcurrent += h;
+        }
+        let end = clock.now() - start;
+        println!("Euler Approximation: {:?}\nTime: {} seconds\n", y, end);
+        (y,end)
+    }
+
+    /* Also known as the Heun method for approximation*/
+    pub fn improved_euler<F:Fn(f64,&[f64],&mut [f64]), C:Clock>(y0:&[f64],t0:f64,steps:usize, h:f64,de:&F, clock:&C)-> (Vec<f64>,f64) {
+        let start = clock.now();
+        let dim = y0.len();
+        let mut y = y0.to_vec();
+        let mut tcurrent = t0;
+        let half_h = h/2.0;
+        let mut k1 = vec![0.0; dim];
+        let mut k2 = vec![0.0; dim];
+        let mut tmp = vec![0.0; dim];
+        for _ in 0..steps {
+            de(tcurrent, &y, &mut k1);
+            for i in 0..dim {
+                tmp[i] = y[i] + h*k1[i];
+            }
+            de(tcurrent + h, &tmp, &mut k2);
+            for i in 0..dim {
+                y[i] += half_h*(k1[i] + k2[i]);
+            }
+            tcurrent += h;
+        }
+        let end = clock.now() - start;
+        println!("Heun Approximation: {:?}\nTime: {} seconds\n", y, end);
+        (y,end)
+    }
+
+    pub fn runge_kutta<F:Fn(f64,&[f64],&mut [f64]), C:Clock>(y0:&[f64],t0:f64,steps:usize, h:f64,de:&F, clock:&C)-> (Vec<f64>,f64) {
+        let start = clock.now();
+        let dim = y0.len();
+        let mut y = y0.to_vec();
+        let mut tcurrent = t0;
+
+        /* Precompute division operations outside of loop for efficiency*/
+        let half_h = h/2.0;
+        let sixth_h = h/6.0;
+        let mut k1 = vec![0.0; dim];
+        let mut k2 = vec![0.0; dim];
+        let mut k3 = vec![0.0; dim];
+        let mut k4 = vec![0.0; dim];
+        let mut tmp = vec![0.0; dim];
+        for _ in 0..steps {
+            de(tcurrent, &y, &mut k1);
+            for i in 0..dim { tmp[i] = y[i] + half_h*k1[i]; }
+            de(tcurrent + half_h, &tmp, &mut k2);
+            for i in 0..dim { tmp[i] = y[i] + half_h*k2[i]; }
+            de(tcurrent + half_h, &tmp, &mut k3);
+            for i in 0..dim { tmp[i] = y[i] + h*k3[i]; }
+            de(tcurrent + h, &tmp, &mut k4);
+            for i in 0..dim {
+                y[i] += sixth_h*(k1[i] + 2.0*k2[i] + 2.0*k3[i] + k4[i]);
+            }
+            tcurrent += h;
+        }
+        let end = clock.now() - start;
+        println!("Runge Kutta Approximation: {:?}\nTime: {} seconds\n", y, end);
+        (y,end)
+    }
+
+    /* Scalar convenience wrappers mirroring the ones in `threaded_funcs`. */
+    pub fn euler_method_scalar<F:Fn(f64,f64)->f64, C:Clock>(y0:f64,t0:f64,steps:usize, h:f64,de:&F, clock:&C)-> (f64,f64) {
+        let wrapped = |t:f64, y:&[f64], out:&mut [f64]| out[0] = de(t, y[0]);
+        let (y, end) = euler_method(&[y0], t0, steps, h, &wrapped, clock);
+        (y[0], end)
+    }
+
+    pub fn improved_euler_scalar<F:Fn(f64,f64)->f64, C:Clock>(y0:f64,t0:f64,steps:usize, h:f64,de:&F, clock:&C)-> (f64,f64) {
+        let wrapped = |t:f64, y:&[f64], out:&mut [f64]| out[0] = de(t, y[0]);
+        let (y, end) = improved_euler(&[y0], t0, steps, h, &wrapped, clock);
+        (y[0], end)
+    }
+
+    pub fn runge_kutta_scalar<F:Fn(f64,f64)->f64, C:Clock>(y0:f64,t0:f64,steps:usize, h:f64,de:&F, clock:&C)-> (f64,f64) {
+        let wrapped = |t:f64, y:&[f64], out:&mut [f64]| out[0] = de(t, y[0]);
+        let (y, end) = runge_kutta(&[y0], t0, steps, h, &wrapped, clock);
+        (y[0], end)
+    }
+
+    /* Adaptive Runge-Kutta-Fehlberg (RK45). See the matching function in `threaded_funcs` for the
+       controller rationale; this is the reference-taking serial counterpart. */
+    pub fn runge_kutta_adaptive<F:Fn(f64,f64)->f64, C:Clock>(y0:f64, t0:f64, t_end:f64, tol:f64, de:&F, clock:&C)-> (f64,f64,usize,usize) {
+        let start = clock.now();
+        let mut ycurrent = y0;
+        let mut tcurrent = t0;
+        let mut h = (t_end - t0) / 100.0;
+        let mut accepted = 0;
+        let mut rejected = 0;
+        while tcurrent < t_end {
+            if tcurrent + h > t_end {
+                h = t_end - tcurrent;
+            }
+            let k1 = h*de(tcurrent, ycurrent);
+            let k2 = h*de(tcurrent + h/4.0, ycurrent + k1/4.0);
+            let k3 = h*de(tcurrent + 3.0*h/8.0, ycurrent + 3.0*k1/32.0 + 9.0*k2/32.0);
+            let k4 = h*de(tcurrent + 12.0*h/13.0, ycurrent + 1932.0*k1/2197.0 - 7200.0*k2/2197.0 + 7296.0*k3/2197.0);
+            let k5 = h*de(tcurrent + h, ycurrent + 439.0*k1/216.0 - 8.0*k2 + 3680.0*k3/513.0 - 845.0*k4/4104.0);
+            let k6 = h*de(tcurrent + h/2.0, ycurrent - 8.0*k1/27.0 + 2.0*k2 - 3544.0*k3/2565.0 + 1859.0*k4/4104.0 - 11.0*k5/40.0);
+            let y4 = ycurrent + 25.0*k1/216.0 + 1408.0*k3/2565.0 + 2197.0*k4/4104.0 - k5/5.0;
+            let y5 = ycurrent + 16.0*k1/135.0 + 6656.0*k3/12825.0 + 28561.0*k4/56430.0 - 9.0*k5/50.0 + 2.0*k6/55.0;
+            let err = (y5 - y4).abs();
+            if err <= tol {
+                tcurrent += h;
+                ycurrent = y5;
+                accepted += 1;
+            } else {
+                rejected += 1;
+            }
+            let h_new = 0.9 * h * (tol / err.max(1e-12)).powf(0.2);
+            h = h_new.max(0.1*h).min(5.0*h);
+        }
+        let end = clock.now() - start;
+        println!("RKF45 Approximation: {}\nTime: {} seconds ({} accepted, {} rejected)\n", ycurrent, end, accepted, rejected);
+        (ycurrent,end,accepted,rejected)
+    }
+}
+
+/* A small benchmark harness so the crate's timing numbers are reproducible instead of a single
+   sample. Each benchmark discards a few warmup iterations, then reports the distribution (mean, min,
+   max, stddev) over the measured trials. */
+pub mod bench {
+    use super::clock::{Clock, RealClock};
+
+    /* Summary statistics over a benchmark's measured iterations, all in seconds. `trials` records how
+       many measured samples actually ran, which may be fewer than requested if the wall-clock budget
+       tripped. */
+    pub struct BenchStats {
+        pub label: String,
+        pub mean: f64,
+        pub min: f64,
+        pub max: f64,
+        pub stddev: f64,
+        pub trials: usize,
+    }
+
+    impl BenchStats {
+        /* Print the distribution in the crate's usual human-readable style. */
+        pub fn report(&self) {
+            println!("{}: mean {} s, min {} s, max {} s, stddev {} s ({} trials)\n",
+                     self.label, self.mean, self.min, self.max, self.stddev, self.trials);
+        }
+    }
+
+    /* Run `f` `warmup` times (discarded) then `trials` times (measured), returning the distribution of
+       measured durations. */
+    pub fn timed_runs<R>(label:&str, trials:usize, warmup:usize, f: impl FnMut() -> R) -> BenchStats {
+        timed_runs_budget(label, trials, warmup, f64::INFINITY, f)
+    }
+
+    /* As `timed_runs`, but stops early once the cumulative measured wall-clock exceeds `budget`
+       seconds -- a competitive-programming style time limit that keeps a pathological configuration
+       from running forever. */
+    pub fn timed_runs_budget<R>(label:&str, trials:usize, warmup:usize, budget:f64, mut f: impl FnMut() -> R) -> BenchStats {
+        let clock = RealClock;
+        for _ in 0..warmup {
+            let _ = f();
+        }
+        let mut samples = Vec::with_capacity(trials);
+        let overall = clock.now();
+        for _ in 0..trials {
+            let start = clock.now();
+            let _ = f();
+            samples.push(clock.now() - start);
+            if clock.now() - overall > budget {
+                break;
+            }
+        }
+        let n = samples.len().max(1) as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let variance = samples.iter().map(|s| (s - mean)*(s - mean)).sum::<f64>() / n;
+        BenchStats {
+            label: label.to_string(),
+            mean,
+            min,
+            max,
+            stddev: variance.sqrt(),
+            trials: samples.len(),
+        }
+    }
+}
+
+/* Sweeping many initial conditions (or parameter values) through the same DE one-thread-per-method
+   barely amortizes the cost of spawning threads. This module keeps a fixed pool of workers alive and
+   feeds them problems from a shared queue, so hundreds of IVPs share the spawn cost of a handful of
+   threads instead of paying it per problem. */
+pub mod batch {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+    use super::threaded_funcs;
+    use super::clock::RealClock;
+
+    /* Which fixed-step method a batch run should dispatch to. Keeping this as data lets the worker
+       loop stay method-agnostic. */
+    #[derive(Clone, Copy)]
+    pub enum Method {
+        Euler,
+        Heun,
+        RungeKutta,
+    }
+
+    impl Method {
+        fn run<F:Fn(f64,f64)->f64>(self, y0:f64, t0:f64, steps:usize, h:f64, de:Arc<F>) -> (f64,f64) {
+            match self {
+                Method::Euler => threaded_funcs::euler_method_scalar(y0, t0, steps, h, de, &RealClock),
+                Method::Heun => threaded_funcs::improved_euler_scalar(y0, t0, steps, h, de, &RealClock),
+                Method::RungeKutta => threaded_funcs::runge_kutta_scalar(y0, t0, steps, h, de, &RealClock),
+            }
+        }
+    }
+
+    /* Each problem is (y0, t0, steps, h). The returned vector is (result, elapsed) aligned with the
+       input order, regardless of the order the workers happen to finish in. */
+    pub fn solve_batch<F>(problems:&[(f64,f64,usize,f64)], de:Arc<F>, method:Method) -> Vec<(f64,f64)>
+        where F: Fn(f64,f64)->f64 + Send + Sync + 'static
+    {
+        let n = problems.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        /* Never spawn more workers than there are problems to chew through. */
+        let workers = available_parallelism().min(n);
+        /* Workers claim the next unsolved index by bumping a shared cursor -- a lock-free work
+           queue that keeps every thread busy until the list is drained. */
+        let cursor = Arc::new(AtomicUsize::new(0));
+        let shared = Arc::new(problems.to_vec());
+        let (tx, rx) = mpsc::channel();
+        let mut handles = Vec::with_capacity(workers);
+        for _ in 0..workers {
+            let cursor = cursor.clone();
+            let shared = shared.clone();
+            let de = de.clone();
+            let tx = tx.clone();
+            handles.push(thread::spawn(move || {
+                loop {
+                    let i = cursor.fetch_add(1, Ordering::Relaxed);
+                    if i >= shared.len() {
+                        break;
+                    }
+                    let (y0, t0, steps, h) = shared[i];
+                    let ans = method.run(y0, t0, steps, h, de.clone());
+                    tx.send((i, ans)).unwrap();
+                }
+            }));
+        }
+        /* Drop the main handle so the receiver closes once every worker is done. */
+        drop(tx);
+        let mut results = vec![(0.0, 0.0); n];
+        for (i, ans) in rx {
+            results[i] = ans;
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        results
+    }
+
+    /* Thin single-problem wrapper so callers with one IVP don't have to build a slice. */
+    pub fn solve<F>(y0:f64, t0:f64, steps:usize, h:f64, de:Arc<F>, method:Method) -> (f64,f64)
+        where F: Fn(f64,f64)->f64 + Send + Sync + 'static
+    {
+        solve_batch(&[(y0, t0, steps, h)], de, method).remove(0)
+    }
+
+    fn available_parallelism() -> usize {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /* A batch sweep returns results aligned with the input order, and each matches the scalar the
+           single-problem wrapper would produce. With a constant slope of 1.0 the Euler result is just
+           `y0 + steps*h`. */
+        #[test]
+        fn solve_batch_is_aligned_and_correct() {
+            let de = Arc::new(|_t: f64, _y: f64| 1.0);
+            let problems = [(0.0, 0.0, 4, 0.5), (1.0, 0.0, 2, 0.5), (10.0, 0.0, 0, 0.5)];
+            let results = solve_batch(&problems, de.clone(), Method::Euler);
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].0, 2.0);
+            assert_eq!(results[1].0, 2.0);
+            assert_eq!(results[2].0, 10.0);
+
+            let single = solve(0.0, 0.0, 4, 0.5, de, Method::Euler);
+            assert_eq!(single.0, results[0].0);
+        }
+    }
+}
+
+/* C-callable wrappers around the numerical core, so the hot integration loops can be reused from
+   other languages. The derivative is supplied as a C function pointer; everything else mirrors the
+   native API. Gated behind the `cffi` cargo feature so the default build carries no `extern "C"`
+   surface.
+
+   Contract: the `de` callback is invoked many times per solve and, on the threaded path, from more
+   than one thread at once, so it MUST be reentrant and thread-safe -- it may not mutate shared state
+   without its own synchronization. */
+#[cfg(feature = "cffi")]
+pub mod ffi {
+    use std::sync::Arc;
+    use super::threaded_funcs;
+    use super::clock::RealClock;
+
+    /// Run the fixed-step Runge-Kutta loop driven by a C derivative and return the final value. The
+    /// elapsed seconds are written through `elapsed_out` when it is non-null.
+    ///
+    /// # Safety
+    ///
+    /// `elapsed_out` must be either null or a valid, writable, aligned `*mut f64`, and `de` must be a
+    /// valid, reentrant, thread-safe function pointer for the whole call.
+    #[no_mangle]
+    pub unsafe extern "C" fn de_solve_runge_kutta(y0:f64, t0:f64, steps:usize, h:f64, de: extern "C" fn(f64,f64)->f64, elapsed_out: *mut f64) -> f64 {
+        let de = Arc::new(move |t:f64, y:f64| de(t, y));
+        let (result, elapsed) = threaded_funcs::runge_kutta_scalar(y0, t0, steps, h, de, &RealClock);
+        if !elapsed_out.is_null() {
+            *elapsed_out = elapsed;
+        }
+        result
+    }
+
+    /// Fixed-step Euler variant of [`de_solve_runge_kutta`].
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`de_solve_runge_kutta`]: `elapsed_out` null-or-valid, `de` valid and
+    /// thread-safe.
+    #[no_mangle]
+    pub unsafe extern "C" fn de_solve_euler(y0:f64, t0:f64, steps:usize, h:f64, de: extern "C" fn(f64,f64)->f64, elapsed_out: *mut f64) -> f64 {
+        let de = Arc::new(move |t:f64, y:f64| de(t, y));
+        let (result, elapsed) = threaded_funcs::euler_method_scalar(y0, t0, steps, h, de, &RealClock);
+        if !elapsed_out.is_null() {
+            *elapsed_out = elapsed;
+        }
+        result
+    }
+
+    /// Improved-Euler (Heun) variant of [`de_solve_runge_kutta`].
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`de_solve_runge_kutta`]: `elapsed_out` null-or-valid, `de` valid and
+    /// thread-safe.
+    #[no_mangle]
+    pub unsafe extern "C" fn de_solve_improved_euler(y0:f64, t0:f64, steps:usize, h:f64, de: extern "C" fn(f64,f64)->f64, elapsed_out: *mut f64) -> f64 {
+        let de = Arc::new(move |t:f64, y:f64| de(t, y));
+        let (result, elapsed) = threaded_funcs::improved_euler_scalar(y0, t0, steps, h, de, &RealClock);
+        if !elapsed_out.is_null() {
+            *elapsed_out = elapsed;
+        }
+        result
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        extern "C" fn constant_slope(_t: f64, _y: f64) -> f64 {
+            1.0
+        }
+
+        /* Driving the C entry point with a constant-slope callback reproduces the native result and
+           writes the elapsed seconds through the out-param. */
+        #[test]
+        fn ffi_euler_matches_native() {
+            let mut elapsed = -1.0;
+            let y = unsafe { de_solve_euler(0.0, 0.0, 4, 0.5, constant_slope, &mut elapsed) };
+            assert_eq!(y, 2.0);
+            assert!(elapsed >= 0.0);
+        }
+    }
+}